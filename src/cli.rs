@@ -0,0 +1,54 @@
+use clap::Parser;
+
+/// Command-line overrides for headless/scripted startup, layered on top of config.toml
+#[derive(Parser, Debug)]
+#[command(name = "mouse-speed-analyser", about = "Mouse speed and polling rate analyser")]
+pub struct Cli {
+    /// Override the mouse DPI used for counts-to-distance conversion
+    #[arg(long, value_parser = parse_positive_dpi)]
+    pub dpi: Option<f64>,
+
+    /// Override the averaging window (in ms) used for the speed calculation
+    #[arg(long, value_parser = parse_positive_window)]
+    pub window: Option<f64>,
+
+    /// Start in basic mode: condensed numbers only, no graphs
+    #[arg(long)]
+    pub basic: bool,
+
+    /// Override the deadzone (in counts) applied to absolute-device deltas
+    #[arg(long, value_parser = parse_non_negative_deadzone)]
+    pub deadzone: Option<i32>,
+}
+
+// DPI feeds `0.0254 / dpi` (meters per count); zero, negative, or NaN would make every speed
+// reading infinite or NaN, so reject it at parse time rather than trusting the caller.
+fn parse_positive_dpi(s: &str) -> Result<f64, String> {
+    parse_positive_f64(s, "DPI")
+}
+
+// The window duration gates `current_time - t > window_duration` when pruning history; zero or
+// negative makes every sample prune immediately (speed permanently reads 0), and NaN makes that
+// comparison never hold (history grows without bound). Reject all three at parse time.
+fn parse_positive_window(s: &str) -> Result<f64, String> {
+    parse_positive_f64(s, "Window duration")
+}
+
+fn parse_positive_f64(s: &str, what: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|_| format!("`{s}` isn't a number"))?;
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("{what} must be positive, got `{s}`"))
+    }
+}
+
+// Zero is a valid deadzone (no filtering); negative has no meaning against i32 delta magnitudes.
+fn parse_non_negative_deadzone(s: &str) -> Result<i32, String> {
+    let value: i32 = s.parse().map_err(|_| format!("`{s}` isn't a whole number"))?;
+    if value >= 0 {
+        Ok(value)
+    } else {
+        Err(format!("Deadzone must not be negative, got `{s}`"))
+    }
+}
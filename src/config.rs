@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+// Global defaults, overridden by whatever is persisted in config.toml
+const DEFAULT_DPI: f64 = 1600.0;
+const DEFAULT_WINDOW_DURATION_MS: f64 = 5.0;
+const DEFAULT_GRAPH_HISTORY_LEN: usize = 1000;
+const DEFAULT_GRAPH_TIME_SPAN: f64 = 5.0;
+// Counts of absolute-device movement this small are dropped as noise rather than diffed into a delta
+const DEFAULT_DEADZONE: i32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub dpi: f64,                      // Mouse DPI used for counts-to-distance conversion
+    pub window_duration_ms: f64,       // Averaging window for instantaneous speed
+    pub graph_history_len: usize,      // Max samples kept per history buffer
+    pub graph_time_span: f64,          // Seconds of history shown on the graphs
+    #[serde(default = "default_deadzone")]
+    pub deadzone: i32,                 // Noise floor applied to absolute-device deltas, see InputFilter
+}
+
+fn default_deadzone() -> i32 {
+    DEFAULT_DEADZONE
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            dpi: DEFAULT_DPI,
+            window_duration_ms: DEFAULT_WINDOW_DURATION_MS,
+            graph_history_len: DEFAULT_GRAPH_HISTORY_LEN,
+            graph_time_span: DEFAULT_GRAPH_TIME_SPAN,
+            deadzone: DEFAULT_DEADZONE,
+        }
+    }
+}
+
+impl Config {
+    // Path to config.toml inside the platform config dir, e.g. %appdata% on Windows
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("mouse-speed-analyser").join("config.toml"))
+    }
+
+    // Loads config.toml if present, creating it with defaults on first run.
+    // Any missing file or parse error falls back to the hardcoded defaults rather than panicking.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_default(),
+            Err(_) => {
+                let config = Self::default();
+                config.save();
+                config
+            }
+        }
+    }
+
+    // Writes the current config back to config.toml, creating parent directories as needed.
+    // Save failures are ignored; they just mean the next launch falls back to defaults again.
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+
+        if let Some(parent) = path.parent() {
+            if fs::create_dir_all(parent).is_err() {
+                return;
+            }
+        }
+
+        if let Ok(contents) = toml::to_string_pretty(self) {
+            let _ = fs::write(path, contents);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+// A single report this far from zero is beyond most relative movement, but high-CPI sensors can
+// exceed it for a few reports during a hard flick (e.g. a 26000 CPI sensor at 2 m/s gives about
+// 2000 counts per report at 1000 Hz, and about 16000 at 125 Hz). An absolute-position device
+// reports large coordinates on *every* report, not just transiently, so requiring several
+// consecutive large reports before committing to absolute mode is what actually separates the
+// two cases rather than the magnitude alone - see SUSTAINED_REPORTS_TO_CONFIRM below.
+const ABSOLUTE_JUMP_THRESHOLD: i32 = 2000;
+// Number of consecutive over-threshold reports required before a device is treated as
+// absolute-position rather than a fast relative flick
+const SUSTAINED_REPORTS_TO_CONFIRM: u32 = 5;
+
+#[derive(Default)]
+struct DeviceState {
+    last_absolute: Option<(i32, i32)>,
+    consecutive_large: u32,
+    confirmed_absolute: bool,
+}
+
+// Converts absolute-position reports (graphics tablets, some touchpads) into relative deltas by
+// diffing against the last known absolute position per device. Sits between
+// `manager.get_event()` and the state update so the rest of the app only ever sees relative
+// deltas, regardless of how the underlying device reports position.
+//
+// A report over ABSOLUTE_JUMP_THRESHOLD is ambiguous on its own - it could be one report of a
+// fast relative flick, or a tablet's absolute coordinate - so while a device is in this unconfirmed
+// window its raw value is never handed back as a delta (that would be a huge jump for a real
+// absolute device); the report is suppressed instead. Only once SUSTAINED_REPORTS_TO_CONFIRM
+// consecutive large reports have been seen does the device commit to absolute mode and start
+// diffing real deltas. This means a few of a relative flick's reports are dropped rather than
+// corrupted - `multiinput` doesn't expose device capabilities to distinguish the two cases
+// directly, so this remains a heuristic and that's the safer failure mode of the two.
+pub struct InputFilter {
+    deadzone: i32,
+    devices: HashMap<usize, DeviceState>,
+}
+
+impl InputFilter {
+    pub fn new(deadzone: i32) -> Self {
+        Self {
+            deadzone,
+            devices: HashMap::new(),
+        }
+    }
+
+    // Takes a raw (device_id, x, y) report and returns the relative delta to apply.
+    pub fn filter(&mut self, device_id: usize, x: i32, y: i32) -> (i32, i32) {
+        let dev = self.devices.entry(device_id).or_default();
+        let is_large = x.abs() > ABSOLUTE_JUMP_THRESHOLD || y.abs() > ABSOLUTE_JUMP_THRESHOLD;
+
+        if !is_large {
+            // Back to ordinary relative reports; drop any absolute-mode state for this device
+            dev.consecutive_large = 0;
+            dev.confirmed_absolute = false;
+            dev.last_absolute = None;
+            return (x, y);
+        }
+
+        if !dev.confirmed_absolute {
+            dev.consecutive_large += 1;
+            if dev.consecutive_large < SUSTAINED_REPORTS_TO_CONFIRM {
+                // Not yet sustained - ambiguous report, suppress it rather than risk handing back
+                // a raw absolute coordinate as a delta
+                return (0, 0);
+            }
+
+            // Sustained large reports confirmed: switch into absolute mode. There's no reliable
+            // prior position to diff the confirming report against, so it emits a zero delta
+            // rather than a huge jump, per the first-sample guard.
+            dev.confirmed_absolute = true;
+            dev.last_absolute = Some((x, y));
+            return (0, 0);
+        }
+
+        let (dx, dy) = match dev.last_absolute {
+            Some((prev_x, prev_y)) => (x - prev_x, y - prev_y),
+            None => (0, 0),
+        };
+        dev.last_absolute = Some((x, y));
+
+        (
+            if dx.abs() < self.deadzone { 0 } else { dx },
+            if dy.abs() < self.deadzone { 0 } else { dy },
+        )
+    }
+}
+
+impl Default for InputFilter {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
@@ -1,29 +1,63 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod cli;
+mod config;
+mod input_filter;
+mod polling;
+mod recording;
+mod scheduler;
+
+use clap::Parser;
+use cli::Cli;
+use config::Config;
 use eframe::egui;
 use egui::Vec2b;
-use egui_plot::{Line, Plot, PlotPoints};
+use egui_plot::{Bar, BarChart, Line, Plot, PlotPoints};
+use input_filter::InputFilter;
 use multiinput::{DeviceType, RawEvent, RawInputManager};
+use polling::PollingStats;
+use recording::{RecordedSample, Recorder, StatsSnapshot};
+use scheduler::Scheduler;
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
+// Scheduler task ids
+const TASK_AUTO_RESET_MAX_SPEED: &str = "auto_reset_max_speed";
+const TASK_SAVE_DPI: &str = "save_dpi";
+const TASK_SAVE_WINDOW: &str = "save_window";
+const TASK_RECORDING_SNAPSHOT: &str = "recording_snapshot";
+
+// No movement for this long auto-resets the displayed max speed
+const AUTO_RESET_MAX_SPEED_DELAY: Duration = Duration::from_secs(3);
+// Debounce delay before a changed DPI/window field is written to config.toml
+const SAVE_DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
+// How often a stats snapshot is taken while a recording is active
+const RECORDING_SNAPSHOT_PERIOD: Duration = Duration::from_secs(1);
+
+// Window sizes for the two display modes, shared between startup (`main`) and the F2 runtime
+// toggle so both paths agree on what "basic mode" looks like.
+const NORMAL_MODE_INNER_SIZE: [f32; 2] = [400.0, 400.0];
+const BASIC_MODE_INNER_SIZE: [f32; 2] = [220.0, 160.0];
+
 // State for app
 #[derive(Default)]
 struct MouseState {
-    events_count: usize,                                // Number of events in the current interval
-    events_per_second: usize,                           // Number of events per second
+    events_per_second: usize,                           // Polling rate, derived from the modal inter-event interval
     delta: (i32, i32),                                  // Mouse delta
     running: bool,                                      // Flag to control the thread
     max_speed: f64,                                     // Maximum speed
     dpi: f64,                                           // DPI
-    last_event_time: Option<Instant>,                   // Time of the last event
+    last_event_time: Option<Instant>,                   // Time of the last counted event
     speed_history: VecDeque<(f64, f64)>,                // History of speed over time
     polling_history: VecDeque<(f64, f64)>,              // History of polling rate over time
     start_time: Option<Instant>,                        // Start time
     event_history: VecDeque<(f64, (i32, i32))>,         // History of mouse events over time
     last_polling_update: Option<Instant>,               // Time of the last polling update
+    polling_stats: PollingStats,                        // Inter-event interval histogram and jitter stats
+    recorder: Recorder,                                 // Captures the full event stream while recording
+    scheduler: Scheduler,                               // Deferred/periodic tasks, drained once per frame
 }
 
 // App struct for UI input fields and state
@@ -31,27 +65,44 @@ struct MouseApp {
     state: Arc<Mutex<MouseState>>,                      // Shared state
     dpi_string: String,                                 // DPI input string
     window_duration_string: String,                     // Window duration input string
+    config: Config,                                     // Persisted settings, saved on change
+    basic_mode: bool,                                   // Condensed overlay mode, set via --basic
+    confirm_discard_recording: bool,                    // Awaiting confirmation to discard an unexported capture
 }
 
 impl MouseApp {
-    fn new() -> Self {
+    fn new(cli: Cli) -> Self {
+        let mut config = Config::load();
+        if let Some(dpi) = cli.dpi {
+            config.dpi = dpi;
+        }
+        if let Some(window) = cli.window {
+            config.window_duration_ms = window;
+        }
+        if let Some(deadzone) = cli.deadzone {
+            config.deadzone = deadzone;
+        }
+
         let state = Arc::new(Mutex::new(MouseState {
-            events_count: 0,
             events_per_second: 0,
             delta: (0, 0),
             running: true,
             max_speed: 0.0,
-            dpi: 1600.0,
+            dpi: config.dpi,
             last_event_time: None,
-            speed_history: VecDeque::with_capacity(1000),
-            polling_history: VecDeque::with_capacity(1000),
+            speed_history: VecDeque::with_capacity(config.graph_history_len),
+            polling_history: VecDeque::with_capacity(config.graph_history_len),
             start_time: Some(Instant::now()),
-            event_history: VecDeque::with_capacity(1000),
+            event_history: VecDeque::with_capacity(config.graph_history_len),
             last_polling_update: Some(Instant::now()),
+            polling_stats: PollingStats::default(),
+            recorder: Recorder::default(),
+            scheduler: Scheduler::default(),
         }));
 
-        let dpi_string = "1600.0".to_string();                        // Default DPI
-        let window_duration_string = "5.0".to_string();               // Default window duration for averaging speed
+        let dpi_string = config.dpi.to_string();                      // DPI, from config.toml or --dpi
+        let window_duration_string = config.window_duration_ms.to_string(); // Window duration, from config.toml or --window
+        let deadzone = config.deadzone;
         let state_clone = state.clone();              // Clone for the polling thread
         let polling_interval = Duration::from_millis(15);           // Interval for polling rate updates
 
@@ -59,6 +110,7 @@ impl MouseApp {
         thread::spawn(move || {
             let mut manager = RawInputManager::new().unwrap();            // Raw input manager
             manager.register_devices(DeviceType::Mice);                                    // Register mice
+            let mut input_filter = InputFilter::new(deadzone); // Normalizes absolute-position reports (tablets/touchpads) to deltas
 
             loop {
                 {
@@ -70,16 +122,45 @@ impl MouseApp {
 
                 // Poll for mouse events
                 if let Some(event) = manager.get_event() {
-                    if let RawEvent::MouseMoveEvent(_, x, y) = event {
+                    if let RawEvent::MouseMoveEvent(device_id, raw_x, raw_y) = event {
+                        let (x, y) = input_filter.filter(device_id, raw_x, raw_y);
                         let now = Instant::now();
                         let mut state = state_clone.lock().unwrap();
                         let elapsed_time = now.duration_since(state.start_time.unwrap()).as_secs_f64();
 
-                        // Calculate speed
-                        state.last_event_time = Some(now);
-                        state.events_count += 1;
+                        // Feed the raw inter-event interval into the polling rate/jitter histogram,
+                        // which collapses sub-microsecond gaps from the same USB poll into one
+                        // sample. Only record a recorder sample for intervals it actually accepts,
+                        // so a collapsed burst duplicate doesn't export as a near-zero interval
+                        // with an absurd derived speed.
+                        if let Some(prev) = state.last_event_time {
+                            let interval = now.duration_since(prev).as_secs_f64();
+
+                            if state.polling_stats.record(interval) {
+                                let meters_per_count = 0.0254 / state.dpi;
+                                let distance = ((x * x + y * y) as f64).sqrt() * meters_per_count;
+                                let inst_speed = distance / interval;
+                                state.recorder.push(RecordedSample {
+                                    timestamp: elapsed_time,
+                                    dx: x,
+                                    dy: y,
+                                    speed: inst_speed,
+                                    polling_interval_ms: interval * 1000.0,
+                                });
+
+                                state.last_event_time = Some(now);
+                            }
+                        } else {
+                            state.last_event_time = Some(now);
+                        }
+
                         state.delta = (x, y);
                         state.event_history.push_back((elapsed_time, (x, y)));
+
+                        // Movement resets the auto-reset timer; it only fires after it goes quiet
+                        state
+                            .scheduler
+                            .schedule_once(TASK_AUTO_RESET_MAX_SPEED, AUTO_RESET_MAX_SPEED_DELAY);
                     }
                 } else {
                     // If no events, sleep for a short time to avoid busy waiting
@@ -90,6 +171,7 @@ impl MouseApp {
 
         // Thread for continuous polling rate updates
         let state_clone = state.clone();
+        let graph_history_len = config.graph_history_len;
         thread::spawn(move || {
             loop {
                 {
@@ -101,19 +183,17 @@ impl MouseApp {
                     let now = Instant::now();
                     let elapsed_time = now.duration_since(state.start_time.unwrap()).as_secs_f64();
                     
-                    // Update polling rate every polling_interval
+                    // Update polling rate every polling_interval, from the modal inter-event interval
                     if let Some(last_update) = state.last_polling_update {
                         if last_update.elapsed() >= polling_interval {
-                            state.events_per_second = (state.events_count as f64 
-                                * (1.0 / polling_interval.as_secs_f64())) as usize;
+                            state.events_per_second = state.polling_stats.polling_rate_hz().round() as usize;
                             let events = state.events_per_second as f64;
                             state.polling_history.push_back((elapsed_time, events));
-                            
-                            if state.polling_history.len() > 1000 {
+
+                            if state.polling_history.len() > graph_history_len {
                                 state.polling_history.pop_front();
                             }
-                            
-                            state.events_count = 0;
+
                             state.last_polling_update = Some(now);
                         }
                     }
@@ -122,10 +202,13 @@ impl MouseApp {
             }
         });
 
-        Self { 
-            state, 
+        Self {
+            state,
             dpi_string,
             window_duration_string,
+            config,
+            basic_mode: cli.basic,
+            confirm_discard_recording: false,
         }
     }
 }
@@ -138,9 +221,27 @@ impl eframe::App for MouseApp {
         let now = Instant::now();
         let current_time = now.duration_since(state.start_time.unwrap()).as_secs_f64();
 
+        // Drain any deferred/periodic tasks due this frame
+        for task_id in state.scheduler.drain_due() {
+            match task_id.as_str() {
+                TASK_AUTO_RESET_MAX_SPEED => state.max_speed = 0.0,
+                TASK_SAVE_DPI => self.config.save(),
+                TASK_SAVE_WINDOW => self.config.save(),
+                TASK_RECORDING_SNAPSHOT => {
+                    let snapshot = StatsSnapshot {
+                        timestamp: current_time,
+                        polling_rate_hz: state.events_per_second as f64,
+                        max_speed: state.max_speed,
+                    };
+                    state.recorder.push_snapshot(snapshot);
+                }
+                _ => {}
+            }
+        }
+
         // Prune histories based on graph_duration
         while let Some(&(t, _)) = state.polling_history.front() {
-            if current_time - t > 5.0 {
+            if current_time - t > self.config.graph_time_span {
                 state.polling_history.pop_front();
             } else {
                 break;
@@ -149,7 +250,7 @@ impl eframe::App for MouseApp {
 
         // Prune speed history based on graph_duration
         while let Some(&(t, _)) = state.speed_history.front() {
-            if current_time - t > 5.0 {
+            if current_time - t > self.config.graph_time_span {
                 state.speed_history.pop_front();
             } else {
                 break;
@@ -191,10 +292,40 @@ impl eframe::App for MouseApp {
 
         // Update speed history
         state.speed_history.push_back((current_time, speed));
-        if state.speed_history.len() > 1000 {
+        if state.speed_history.len() > self.config.graph_history_len {
             state.speed_history.pop_front();
         }
 
+        // F2 toggles basic mode: a compact, always-on-top overlay with just the headline
+        // numbers, no graphs, sized to sit in a corner while the user is in a game
+        if ctx.input(|i| i.key_pressed(egui::Key::F2)) {
+            self.basic_mode = !self.basic_mode;
+
+            let (size, level) = if self.basic_mode {
+                (BASIC_MODE_INNER_SIZE, egui::WindowLevel::AlwaysOnTop)
+            } else {
+                (NORMAL_MODE_INNER_SIZE, egui::WindowLevel::Normal)
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size.into()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        }
+
+        if self.basic_mode {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    ui.label(format!("Speed: {:.4} m/s", speed));
+                    ui.label(format!("Max Speed: {:.4} m/s", state.max_speed));
+                    ui.label(format!("Polling Rate: {}", state.events_per_second));
+                    ui.label(format!("Delta X: {}, Delta Y: {}", state.delta.0, state.delta.1));
+                });
+            });
+            return;
+        }
+
+        // Set when an export button is clicked; the actual (blocking) file dialog and write
+        // happen after this closure returns, once the state lock below has been released
+        let mut export_request: Option<&'static str> = None;
+
         // Update UI
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Mouse Speed Analyser");
@@ -210,16 +341,28 @@ impl eframe::App for MouseApp {
                     if let Ok(new_dpi) = self.dpi_string.parse::<f64>() {
                         if new_dpi > 0.0 {
                             state.dpi = new_dpi;
+                            self.config.dpi = new_dpi;
+                            // Debounced: only hits disk once typing has paused
+                            state.scheduler.schedule_once(TASK_SAVE_DPI, SAVE_DEBOUNCE_DELAY);
                         }
                     }
                 }
 
                 ui.label("Window for averaging speed (ms):");
-                ui.add(
+                let window_response = ui.add(
                     egui::TextEdit::singleline(&mut self.window_duration_string)
                         .desired_width(60.0)
                         .hint_text("Speed calculation window"),
                 );
+                if window_response.changed() {
+                    if let Ok(new_window) = self.window_duration_string.parse::<f64>() {
+                        if new_window > 0.0 {
+                            self.config.window_duration_ms = new_window;
+                            // Debounced: only hits disk once typing has paused
+                            state.scheduler.schedule_once(TASK_SAVE_WINDOW, SAVE_DEBOUNCE_DELAY);
+                        }
+                    }
+                }
             });
 
             ui.label(format!(
@@ -232,15 +375,70 @@ impl eframe::App for MouseApp {
             ));
             ui.label(format!("Speed: {:.4} m/s", speed));
             ui.label(format!("Max Speed: {:.4} m/s", state.max_speed));
+            ui.label(format!(
+                "Jitter: min {:.3}ms, max {:.3}ms, mean {:.3}ms, stddev {:.3}ms, {:.1}% outliers",
+                state.polling_stats.min_secs() * 1000.0,
+                state.polling_stats.max_secs() * 1000.0,
+                state.polling_stats.mean_secs() * 1000.0,
+                state.polling_stats.stddev_secs() * 1000.0,
+                state.polling_stats.outlier_fraction() * 100.0,
+            ));
 
             if ui.button("Reset Max Speed").clicked() {
                 state.max_speed = 0.0;
             }
 
+            ui.horizontal(|ui| {
+                if state.recorder.is_recording() {
+                    if ui.button("Stop Recording").clicked() {
+                        state.recorder.stop();
+                        state.scheduler.cancel(TASK_RECORDING_SNAPSHOT);
+                    }
+                    ui.label(format!("Recording... {} samples", state.recorder.len()));
+                } else if self.confirm_discard_recording {
+                    ui.label("Discard the unexported capture?");
+                    if ui.button("Discard & Start Recording").clicked() {
+                        state.recorder.start();
+                        state
+                            .scheduler
+                            .schedule_repeating(TASK_RECORDING_SNAPSHOT, RECORDING_SNAPSHOT_PERIOD);
+                        self.confirm_discard_recording = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_discard_recording = false;
+                    }
+                } else {
+                    if ui.button("Start Recording").clicked() {
+                        if state.recorder.is_empty() {
+                            state.recorder.start();
+                            state
+                                .scheduler
+                                .schedule_repeating(TASK_RECORDING_SNAPSHOT, RECORDING_SNAPSHOT_PERIOD);
+                        } else {
+                            // There's an unexported capture sitting in the recorder; starting over
+                            // would silently throw it away, so make the user confirm first.
+                            self.confirm_discard_recording = true;
+                        }
+                    }
+
+                    if !state.recorder.is_empty() {
+                        ui.label(format!("{} samples captured", state.recorder.len()));
+
+                        if ui.button("Export CSV").clicked() {
+                            export_request = Some("csv");
+                        }
+
+                        if ui.button("Export JSON").clicked() {
+                            export_request = Some("json");
+                        }
+                    }
+                }
+            });
+
             ui.separator();
 
             // Graphs
-            ui.columns(2, |columns| {
+            ui.columns(3, |columns| {
                 columns[0].label(format!("Speed Over Time"));
                 Plot::new("speed_plot")
                     .allow_zoom(Vec2b::FALSE)
@@ -272,16 +470,72 @@ impl eframe::App for MouseApp {
                             state.polling_history.iter().map(|&(x, y)| [x, y]).collect();
                         plot_ui.line(Line::new(points).fill(0.0));
                     });
+
+                columns[2].label(format!("Interval Histogram"));
+                Plot::new("interval_histogram_plot")
+                    .allow_zoom(Vec2b::FALSE)
+                    .allow_drag(Vec2b::FALSE)
+                    .allow_scroll(Vec2b::FALSE)
+                    .allow_double_click_reset(false)
+                    .allow_boxed_zoom(false)
+                    .show_grid(false)
+                    .view_aspect(1.0)
+                    .show(&mut columns[2], |plot_ui| {
+                        let bars: Vec<Bar> = state
+                            .polling_stats
+                            .histogram_bars_ms()
+                            .into_iter()
+                            .map(|(interval_ms, count)| Bar::new(interval_ms, count as f64).width(0.1))
+                            .collect();
+                        plot_ui.bar_chart(BarChart::new(bars));
+                    });
             });
         });
+
+        // Clone the data needed for the export out of the recorder, then release the state lock
+        // before the blocking save-file dialog, so the mouse/polling threads don't stall on it
+        if let Some(kind) = export_request {
+            let samples = state.recorder.samples().to_vec();
+            let snapshots = state.recorder.snapshots().to_vec();
+            drop(state);
+
+            let path = match kind {
+                "csv" => rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .set_file_name("mouse_capture.csv")
+                    .save_file(),
+                _ => rfd::FileDialog::new()
+                    .add_filter("JSON", &["json"])
+                    .set_file_name("mouse_capture.json")
+                    .save_file(),
+            };
+
+            if let Some(path) = path {
+                let _ = match kind {
+                    "csv" => recording::export_csv(&samples, &snapshots, &path),
+                    _ => recording::export_json(&samples, &snapshots, &path),
+                };
+            }
+        }
     }
 }
 
 fn main() -> eframe::Result {
+    let cli = Cli::parse();
+
+    // Basic mode drops the graphs down to a small always-on-top overlay, sized to sit in a
+    // corner while the user is in a game
+    let (inner_size, window_level) = if cli.basic {
+        (BASIC_MODE_INNER_SIZE, egui::WindowLevel::AlwaysOnTop)
+    } else {
+        (NORMAL_MODE_INNER_SIZE, egui::WindowLevel::Normal)
+    };
+
     // Initialize eframe
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([400.0, 400.0])
+            .with_inner_size(inner_size)
+            .with_window_level(window_level)
             .with_title("Mouse Speed Analyser"),
         ..Default::default()
     };
@@ -290,6 +544,6 @@ fn main() -> eframe::Result {
     eframe::run_native(
         "Mouse Speed Analyser Analyser",
         options,
-        Box::new(|_cc| Ok(Box::new(MouseApp::new()))),
+        Box::new(|_cc| Ok(Box::new(MouseApp::new(cli)))),
     )
 }
\ No newline at end of file
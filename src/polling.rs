@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+// Width of an interval histogram bucket, in seconds (0.1 ms)
+const BUCKET_WIDTH_SECS: f64 = 0.0001;
+// Gaps tighter than this come from the same USB poll firing multiple move reports,
+// not a distinct sample, so they're collapsed rather than counted.
+const COALESCE_THRESHOLD_SECS: f64 = 0.000_001;
+// An interval is considered jitter when it deviates from the modal interval by more than this
+const OUTLIER_DEVIATION: f64 = 0.25;
+
+// Tracks inter-event timestamp gaps so the true polling rate and jitter can be derived
+// from the most common interval, rather than aliasing off a fixed counting window.
+#[derive(Default)]
+pub struct PollingStats {
+    histogram: HashMap<i64, usize>, // bucket index (interval / BUCKET_WIDTH_SECS) -> count
+    count: usize,
+    sum: f64,
+    sum_sq: f64,
+    min: f64,
+    max: f64,
+}
+
+impl PollingStats {
+    // Records one inter-event interval. Returns false (and records nothing) if the interval
+    // is small enough to be the same USB poll burst rather than a new sample.
+    pub fn record(&mut self, interval_secs: f64) -> bool {
+        if interval_secs < COALESCE_THRESHOLD_SECS {
+            return false;
+        }
+
+        let bucket = (interval_secs / BUCKET_WIDTH_SECS).round() as i64;
+        *self.histogram.entry(bucket).or_insert(0) += 1;
+
+        self.min = if self.count == 0 { interval_secs } else { self.min.min(interval_secs) };
+        self.max = if self.count == 0 { interval_secs } else { self.max.max(interval_secs) };
+        self.count += 1;
+        self.sum += interval_secs;
+        self.sum_sq += interval_secs * interval_secs;
+
+        true
+    }
+
+    // The most common inter-event interval, in seconds
+    fn mode_interval_secs(&self) -> Option<f64> {
+        self.histogram
+            .iter()
+            .max_by_key(|&(_, count)| *count)
+            .map(|(&bucket, _)| bucket as f64 * BUCKET_WIDTH_SECS)
+    }
+
+    // Polling rate derived from the modal interval, in Hz
+    pub fn polling_rate_hz(&self) -> f64 {
+        match self.mode_interval_secs() {
+            Some(interval) if interval > 0.0 => 1.0 / interval,
+            _ => 0.0,
+        }
+    }
+
+    pub fn min_secs(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max_secs(&self) -> f64 {
+        self.max
+    }
+
+    pub fn mean_secs(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+
+    pub fn stddev_secs(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let mean = self.mean_secs();
+        ((self.sum_sq / self.count as f64) - mean * mean).max(0.0).sqrt()
+    }
+
+    // Fraction of recorded intervals deviating more than OUTLIER_DEVIATION from the mode
+    pub fn outlier_fraction(&self) -> f64 {
+        let (Some(mode), true) = (self.mode_interval_secs(), self.count > 0) else {
+            return 0.0;
+        };
+        if mode == 0.0 {
+            return 0.0;
+        }
+
+        let outliers: usize = self
+            .histogram
+            .iter()
+            .filter(|&(&bucket, _)| {
+                let interval = bucket as f64 * BUCKET_WIDTH_SECS;
+                ((interval - mode) / mode).abs() > OUTLIER_DEVIATION
+            })
+            .map(|(_, &count)| count)
+            .sum();
+
+        outliers as f64 / self.count as f64
+    }
+
+    // Histogram bars as (interval in ms, count), sorted by interval, ready to plot
+    pub fn histogram_bars_ms(&self) -> Vec<(f64, usize)> {
+        let mut bars: Vec<(f64, usize)> = self
+            .histogram
+            .iter()
+            .map(|(&bucket, &count)| (bucket as f64 * BUCKET_WIDTH_SECS * 1000.0, count))
+            .collect();
+        bars.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        bars
+    }
+}
@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// One captured mouse event: its delta as passed through InputFilter, plus the per-event
+// speed/interval derivation
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RecordedSample {
+    pub timestamp: f64,
+    pub dx: i32,
+    pub dy: i32,
+    pub speed: f64,
+    pub polling_interval_ms: f64,
+}
+
+// A periodic checkpoint of the headline stats, taken while a recording is active
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StatsSnapshot {
+    pub timestamp: f64,
+    pub polling_rate_hz: f64,
+    pub max_speed: f64,
+}
+
+// Captures the full, unpruned event stream while active, for later offline analysis
+#[derive(Default)]
+pub struct Recorder {
+    recording: bool,
+    samples: Vec<RecordedSample>,
+    snapshots: Vec<StatsSnapshot>,
+}
+
+impl Recorder {
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    // Starts a fresh recording, discarding any previously captured samples
+    pub fn start(&mut self) {
+        self.samples.clear();
+        self.snapshots.clear();
+        self.recording = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.recording = false;
+    }
+
+    pub fn push(&mut self, sample: RecordedSample) {
+        if self.recording {
+            self.samples.push(sample);
+        }
+    }
+
+    pub fn push_snapshot(&mut self, snapshot: StatsSnapshot) {
+        if self.recording {
+            self.snapshots.push(snapshot);
+        }
+    }
+
+    pub fn samples(&self) -> &[RecordedSample] {
+        &self.samples
+    }
+
+    pub fn snapshots(&self) -> &[StatsSnapshot] {
+        &self.snapshots
+    }
+
+    // Writes the raw samples to `path`, plus a sibling "<stem>_snapshots.<ext>" CSV of the
+    // periodic stats snapshots taken during the recording, if any were captured.
+    pub fn export_csv(&self, path: &Path) -> io::Result<()> {
+        export_csv(&self.samples, &self.snapshots, path)
+    }
+
+    // Writes both the raw samples and the periodic stats snapshots as one JSON object
+    pub fn export_json(&self, path: &Path) -> io::Result<()> {
+        export_json(&self.samples, &self.snapshots, path)
+    }
+}
+
+// Free-standing so a caller can clone the data out of a locked `Recorder` and export it without
+// holding that lock across the (blocking) file-save dialog and write.
+pub fn export_csv(samples: &[RecordedSample], snapshots: &[StatsSnapshot], path: &Path) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "timestamp,dx,dy,speed,polling_interval_ms")?;
+    for sample in samples {
+        writeln!(
+            file,
+            "{},{},{},{},{}",
+            sample.timestamp, sample.dx, sample.dy, sample.speed, sample.polling_interval_ms
+        )?;
+    }
+
+    if !snapshots.is_empty() {
+        let mut snapshots_file = File::create(sibling_path(path, "snapshots"))?;
+        writeln!(snapshots_file, "timestamp,polling_rate_hz,max_speed")?;
+        for snapshot in snapshots {
+            writeln!(
+                snapshots_file,
+                "{},{},{}",
+                snapshot.timestamp, snapshot.polling_rate_hz, snapshot.max_speed
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn export_json(samples: &[RecordedSample], snapshots: &[StatsSnapshot], path: &Path) -> io::Result<()> {
+    #[derive(Serialize)]
+    struct Export<'a> {
+        samples: &'a [RecordedSample],
+        snapshots: &'a [StatsSnapshot],
+    }
+
+    let json = serde_json::to_string_pretty(&Export { samples, snapshots })
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    std::fs::write(path, json)
+}
+
+// Builds "<dir>/<stem>_<suffix>.<ext>" next to `path`, e.g. "capture.csv" -> "capture_snapshots.csv"
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("export");
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("csv");
+    let mut sibling = path.to_path_buf();
+    sibling.set_file_name(format!("{stem}_{suffix}.{ext}"));
+    sibling
+}
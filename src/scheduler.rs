@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// A task staged to run at a later instant, repeating if `period` is set
+struct Task {
+    due: Instant,
+    period: Option<Duration>,
+}
+
+// Time-ordered queue of deferred/periodic work, drained once per `update()` frame rather than
+// run inline in the polling threads. Tasks are keyed by id so a pending one can be cancelled or
+// rescheduled, e.g. when a debounced input field changes again before its timer fires.
+#[derive(Default)]
+pub struct Scheduler {
+    tasks: HashMap<String, Task>,
+}
+
+impl Scheduler {
+    // Schedules a one-shot task to fire after `delay`, replacing any existing task with this id
+    pub fn schedule_once(&mut self, id: impl Into<String>, delay: Duration) {
+        self.tasks.insert(
+            id.into(),
+            Task {
+                due: Instant::now() + delay,
+                period: None,
+            },
+        );
+    }
+
+    // Schedules a task that fires every `period`, starting one `period` from now
+    pub fn schedule_repeating(&mut self, id: impl Into<String>, period: Duration) {
+        self.tasks.insert(
+            id.into(),
+            Task {
+                due: Instant::now() + period,
+                period: Some(period),
+            },
+        );
+    }
+
+    pub fn cancel(&mut self, id: &str) {
+        self.tasks.remove(id);
+    }
+
+    pub fn is_scheduled(&self, id: &str) -> bool {
+        self.tasks.contains_key(id)
+    }
+
+    // Returns the ids due by now, earliest due first. Repeating tasks are rescheduled for their
+    // next period; one-shot tasks are removed.
+    pub fn drain_due(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        let mut due: Vec<(Instant, String)> = self
+            .tasks
+            .iter()
+            .filter(|(_, task)| task.due <= now)
+            .map(|(id, task)| (task.due, id.clone()))
+            .collect();
+        due.sort_by_key(|(due_at, _)| *due_at);
+        let due_ids: Vec<String> = due.into_iter().map(|(_, id)| id).collect();
+
+        for id in &due_ids {
+            match self.tasks.get_mut(id) {
+                Some(task) if task.period.is_some() => task.due = now + task.period.unwrap(),
+                _ => {
+                    self.tasks.remove(id);
+                }
+            }
+        }
+
+        due_ids
+    }
+}